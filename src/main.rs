@@ -2,6 +2,17 @@ use std::time::Instant;
 
 // === Configuration ===
 const NUM_ROUNDS: usize = 24;
+
+// Sponge geometry: the first RATE words are attacker-controllable (input is
+// absorbed here and `xof` squeezes its output from here), the last CAPACITY words
+// are never touched by input and provide the security margin, mirroring the
+// duplex/sponge discipline used by Ascon.
+const RATE: usize = 4;
+const CAPACITY: usize = 8 - RATE;
+
+// Reduced round count for the fast, non-cryptographic keyed mode, mirroring
+// the "2-4"-style fast variant SipHash offers alongside its full-strength one.
+const FAST_ROUNDS: usize = 4;
 const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
     0x243F6A8885A308D3, 0x13198A2E03707344, 0xA4093822299F31D0, 0x082EFA98EC4E6C89,
     0x452821E638D01377, 0xBE5466CF34E90C6C, 0xC0AC29B7C97C50DD, 0x3F84D5B5B5470917,
@@ -102,43 +113,477 @@ fn permute_round(state: &mut [u64; 8], round: usize, sbox: &[u8; 256], input_byt
     }
 }
 
-// Complete permutation with dynamic S-Box (from initial state seed + input bytes)
-fn permute(state: &mut [u64; 8], input_bytes: &[u8]) {
+// Complete permutation with dynamic S-Box (from initial state seed + input
+// bytes). `ROUNDS` is a const-generic knob on the round count, the way
+// SipHash exposes `SipHash<C, D>`: instantiate with `NUM_ROUNDS` for the full
+// strong permutation, or `FAST_ROUNDS` for a fast reduced-round variant
+// suitable for non-cryptographic keyed hashing.
+fn permute<const ROUNDS: usize>(state: &mut [u64; 8], input_bytes: &[u8]) {
     let seed = state.iter().fold(0u64, |acc, &v| acc ^ v);
     let sbox = generate_sbox(seed, input_bytes);
 
-    for round in 0..NUM_ROUNDS {
+    for round in 0..ROUNDS {
         permute_round(state, round, &sbox, input_bytes);
     }
 }
 
-// Padding
-fn pad_block(data: &[u8]) -> Vec<u8> {
+// Final-block padding with Merkle-Damgard strengthening (SHA-256/512 style):
+// append a single 0x80 byte, zero-pad, then write the total message
+// bit-length as a fixed 8-byte big-endian field at the very end of the
+// padded data, i.e. in the last 8 bytes of the final rate block. If the
+// data and the 0x80 byte leave fewer than 8 bytes free in the last rate
+// block, one extra block of zero padding plus the length field is produced
+// (and so one extra permutation). Binding the output to the exact
+// input length closes the padding-collision / length-extension gaps that a
+// bare 0x80-then-zeros scheme leaves open.
+fn pad_final_block(data: &[u8], total_bit_len: u64) -> Vec<u8> {
     let mut padded = data.to_vec();
     padded.push(0x80);
-    while padded.len() % 8 != 0 {
+    while padded.len() % BLOCK_BYTES != BLOCK_BYTES - 8 {
         padded.push(0x00);
     }
+    padded.extend_from_slice(&total_bit_len.to_be_bytes());
     padded
 }
 
+fn word_from_le_bytes(chunk: &[u8]) -> u64 {
+    let mut val = 0u64;
+    for (j, &b) in chunk.iter().enumerate() {
+        val |= (b as u64) << (j * 8);
+    }
+    val
+}
+
+// Absorb one rate-sized block of input words into the state and permute.
+// Only state[0..RATE] is ever XORed with attacker-controlled data;
+// state[RATE..8] (the capacity) is left untouched here.
+fn absorb_rate_block<const ROUNDS: usize>(state: &mut [u64; 8], block: &[u64; RATE], input_bytes: &[u8]) {
+    for i in 0..RATE {
+        state[i] ^= block[i];
+    }
+    permute::<ROUNDS>(state, input_bytes);
+}
+
+// Apply final-block padding to `data` (using `total_bit_len` as the encoded
+// message length), split it into rate-sized word blocks and absorb each one
+// in turn.
+fn absorb_final<const ROUNDS: usize>(state: &mut [u64; 8], data: &[u8], total_bit_len: u64) {
+    let padded = pad_final_block(data, total_bit_len);
+    let mut words: Vec<u64> = padded.chunks(8).map(word_from_le_bytes).collect();
+    while words.len() % RATE != 0 {
+        words.push(0u64);
+    }
+
+    for (i, rate_chunk) in words.chunks(RATE).enumerate() {
+        let mut rate_block = [0u64; RATE];
+        rate_block.copy_from_slice(rate_chunk);
+        let start = i * BLOCK_BYTES;
+        let end = (start + BLOCK_BYTES).min(padded.len());
+        absorb_rate_block::<ROUNDS>(state, &rate_block, &padded[start..end]);
+    }
+}
+
 // Hash function
 fn hash(inputs: &[&[u8]]) -> [u64; 8] {
-    let mut state = [0u64; 8];
+    debug_assert_eq!(RATE + CAPACITY, 8, "rate and capacity must cover the full state");
+    let mut engine = HashEngine::<NUM_ROUNDS>::new();
     for &block in inputs {
-        let padded = pad_block(block);
-        for (i, chunk) in padded.chunks(8).enumerate() {
-            let mut val = 0u64;
-            for (j, &b) in chunk.iter().enumerate() {
-                val |= (b as u64) << (j * 8);
+        engine.input(block);
+    }
+    engine.finalize()
+}
+
+// Number of raw bytes absorbed per permutation call, i.e. the rate expressed
+// in bytes rather than words.
+const BLOCK_BYTES: usize = RATE * 8;
+
+// Streaming hash engine: absorbs data incrementally instead of requiring the
+// whole message up front, in the style of rust-bitcoin's `HashEngine`. Full
+// rate-sized blocks are absorbed as soon as enough input has arrived; any
+// leftover bytes are padded and absorbed on `finalize`. `ROUNDS` picks the
+// permutation strength, defaulting to the full-strength mode.
+#[derive(Clone)]
+struct HashEngine<const ROUNDS: usize = NUM_ROUNDS> {
+    state: [u64; 8],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+impl<const ROUNDS: usize> HashEngine<ROUNDS> {
+    fn new() -> Self {
+        Self::with_state([0u64; 8])
+    }
+
+    // Start from a caller-supplied initial state, e.g. one already seeded
+    // with a key for `keyed_hash`.
+    fn with_state(state: [u64; 8]) -> Self {
+        HashEngine { state, buffer: Vec::with_capacity(BLOCK_BYTES), length: 0 }
+    }
+
+    fn input(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+
+        let mut offset = 0;
+        while self.buffer.len() - offset >= BLOCK_BYTES {
+            let chunk = &self.buffer[offset..offset + BLOCK_BYTES];
+            let mut rate_block = [0u64; RATE];
+            for (i, word) in chunk.chunks(8).map(word_from_le_bytes).enumerate() {
+                rate_block[i] = word;
             }
-            state[i % 8] ^= val;
+            absorb_rate_block::<ROUNDS>(&mut self.state, &rate_block, chunk);
+            offset += BLOCK_BYTES;
+        }
+        self.buffer.drain(0..offset);
+    }
+
+    // Returns the raw chaining state, rate and capacity alike -- fine for an
+    // unkeyed digest or `xof`'s squeeze, but unsafe as a keyed/MAC output
+    // (see `keyed_hash_with_rounds`).
+    fn finalize(mut self) -> [u64; 8] {
+        let remainder = std::mem::take(&mut self.buffer);
+        let total_bit_len = self.length.wrapping_mul(8);
+        absorb_final::<ROUNDS>(&mut self.state, &remainder, total_bit_len);
+        self.state
+    }
+
+    // Total number of bytes fed to the engine so far, across all `input()` calls.
+    fn len(&self) -> u64 {
+        self.length
+    }
+}
+
+// Keyed hash / MAC: the key seeds the capacity words (never touched by
+// input) before the permutation runs once and absorption begins, in the
+// style of SipHash's keyed design. `ROUNDS` selects the permutation
+// strength; `keyed_hash` uses the full-strength mode and `keyed_hash_fast`
+// the reduced-round one, suitable for seeding hash tables.
+//
+// `HashEngine::finalize` exposes the rate (see its doc comment), so after
+// `finalize` we re-absorb the key and permute once more and squeeze out
+// only the capacity words as the tag, the same key-then-squeeze shape
+// `finalize_tag` uses for the AEAD tag.
+fn keyed_hash_with_rounds<const ROUNDS: usize>(key: &[u8], inputs: &[&[u8]]) -> [u64; CAPACITY] {
+    let mut state = [0u64; 8];
+    for (i, chunk) in key.chunks(8).take(CAPACITY).enumerate() {
+        state[RATE + i] ^= word_from_le_bytes(chunk);
+    }
+    permute::<ROUNDS>(&mut state, key);
+
+    let mut engine = HashEngine::<ROUNDS>::with_state(state);
+    for &block in inputs {
+        engine.input(block);
+    }
+    let mut state = engine.finalize();
+
+    for (i, chunk) in key.chunks(8).take(CAPACITY).enumerate() {
+        state[RATE + i] ^= word_from_le_bytes(chunk);
+    }
+    permute::<ROUNDS>(&mut state, key);
+
+    let mut tag = [0u64; CAPACITY];
+    tag.copy_from_slice(&state[RATE..]);
+    tag
+}
+
+fn keyed_hash(key: &[u8], inputs: &[&[u8]]) -> [u64; CAPACITY] {
+    keyed_hash_with_rounds::<NUM_ROUNDS>(key, inputs)
+}
+
+fn keyed_hash_fast(key: &[u8], inputs: &[&[u8]]) -> [u64; CAPACITY] {
+    keyed_hash_with_rounds::<FAST_ROUNDS>(key, inputs)
+}
+
+// Extendable-output (XOF) squeeze: absorb `inputs` exactly like `hash`, then
+// stream `out.len()` bytes by repeatedly emitting the rate words and
+// permuting between emissions -- the squeeze phase of the sponge paradigm.
+// The same construction yields a 256-bit digest, 64-byte KDF output, or a
+// stream-cipher keystream, all from the one audited permutation.
+fn xof(inputs: &[&[u8]], out: &mut [u8]) {
+    let mut engine = HashEngine::<NUM_ROUNDS>::new();
+    for &block in inputs {
+        engine.input(block);
+    }
+    let mut state = engine.finalize();
+
+    let mut offset = 0;
+    while offset < out.len() {
+        let squeezed = rate_keystream(&state);
+        let take = (out.len() - offset).min(BLOCK_BYTES);
+        out[offset..offset + take].copy_from_slice(&squeezed[..take]);
+        offset += take;
+
+        if offset < out.len() {
+            permute::<NUM_ROUNDS>(&mut state, &squeezed);
         }
-        permute(&mut state, &padded);
     }
+}
+
+// === core::hash::Hasher / BuildHasher integration ===
+//
+// Lets the permutation back a `HashMap` directly, the way ahash does: keys
+// are folded through the fast reduced-round mode (collision-quality on
+// `HashMap` keys does not need the full cryptographic strength), and
+// `FrostBuildHasher` seeds the capacity from a per-instance random key so
+// that repeated runs (and therefore an attacker probing for hash-flooding
+// collisions) can't predict the hasher's behaviour.
+
+// Wraps a `HashEngine` to implement `core::hash::Hasher`.
+#[derive(Clone)]
+struct FrostHasher {
+    engine: HashEngine<FAST_ROUNDS>,
+}
+
+impl FrostHasher {
+    fn with_key(key: &[u8]) -> Self {
+        let mut state = [0u64; 8];
+        for (i, chunk) in key.chunks(8).take(CAPACITY).enumerate() {
+            state[RATE + i] ^= word_from_le_bytes(chunk);
+        }
+        permute::<FAST_ROUNDS>(&mut state, key);
+        FrostHasher { engine: HashEngine::with_state(state) }
+    }
+}
+
+impl std::hash::Hasher for FrostHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.engine.input(bytes);
+    }
+
+    // `Hasher::finish` takes `&self` and may be called repeatedly without
+    // consuming the running hash, so finalize a clone rather than `self`.
+    fn finish(&self) -> u64 {
+        let digest = self.engine.clone().finalize();
+        digest.iter().fold(0u64, |acc, &word| acc ^ word)
+    }
+}
+
+// Generates a fresh per-instance key from the OS randomness `std` already
+// exposes via `RandomState`, rather than pulling in a dedicated RNG crate.
+fn random_capacity_key() -> [u8; CAPACITY * 8] {
+    use std::hash::{BuildHasher, Hasher};
+    let mut key = [0u8; CAPACITY * 8];
+    for chunk in key.chunks_mut(8) {
+        let word = std::collections::hash_map::RandomState::new().build_hasher().finish();
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    key
+}
+
+// `BuildHasher` that seeds each `FrostHasher` it creates from the same
+// per-instance random key, so e.g. `HashMap::with_hasher(FrostBuildHasher::new())`
+// gets DoS-resistant keyed hashing for free.
+#[derive(Clone)]
+struct FrostBuildHasher {
+    key: [u8; CAPACITY * 8],
+}
+
+impl FrostBuildHasher {
+    fn new() -> Self {
+        FrostBuildHasher { key: random_capacity_key() }
+    }
+}
+
+impl std::hash::BuildHasher for FrostBuildHasher {
+    type Hasher = FrostHasher;
+
+    fn build_hasher(&self) -> FrostHasher {
+        FrostHasher::with_key(&self.key)
+    }
+}
+
+// === Sponge-based authenticated encryption (AEAD) ===
+//
+// Built on the same duplex sponge discipline as the hash and `permute` as
+// the shared round function, in the style of Ascon: the state is seeded
+// from an IV, the key and the nonce, associated data is absorbed into the
+// rate with domain separation, plaintext is encrypted block-by-block by
+// XORing it into the rate (producing ciphertext that then replaces the
+// rate before the next permute), and a key-dependent finalization squeezes
+// an authentication tag out of the capacity.
+
+// Domain-separation constants XORed into the first capacity word before
+// each phase, so that absorbing associated data, encrypting, and
+// finalizing never collide with one another even on identical inputs.
+// Chosen with no XOR relationship between them (e.g. DOMAIN_AD ==
+// DOMAIN_PT ^ DOMAIN_FINAL would let an all-AD or all-PT phase land on
+// the same state word a FINAL phase would), unlike small sequential
+// constants such as 0x01/0x02/0x03.
+const DOMAIN_AD: u64 = 0x1D4A2F6E9C3B58E1;
+const DOMAIN_PT: u64 = 0x7F3C91A5D06B2E48;
+const DOMAIN_FINAL: u64 = 0x52E8A13F6C9D047B;
+
+// Spells "FROSTAE" in ASCII, identifying this as the AEAD instantiation of
+// the permutation rather than the plain hash.
+const AEAD_IV: u64 = 0x46524F53544145;
+
+const TAG_BYTES: usize = CAPACITY * 8;
+
+fn init_aead_state(key: &[u8], nonce: &[u8]) -> [u64; 8] {
+    let mut state = [0u64; 8];
+    state[0] ^= AEAD_IV;
+    for (i, chunk) in key.chunks(8).take(CAPACITY).enumerate() {
+        state[RATE + i] ^= word_from_le_bytes(chunk);
+    }
+    for (i, chunk) in nonce.chunks(8).take(RATE).enumerate() {
+        state[i] ^= word_from_le_bytes(chunk);
+    }
+    permute::<NUM_ROUNDS>(&mut state, key);
     state
 }
 
+fn absorb_associated_data(state: &mut [u64; 8], associated_data: &[u8]) {
+    state[RATE] ^= DOMAIN_AD;
+    absorb_final::<NUM_ROUNDS>(state, associated_data, (associated_data.len() as u64) * 8);
+}
+
+// Little-endian bytes of the current rate words, i.e. the keystream block
+// used to encrypt/decrypt the next BLOCK_BYTES of data.
+fn rate_keystream(state: &[u64; 8]) -> [u8; BLOCK_BYTES] {
+    let mut bytes = [0u8; BLOCK_BYTES];
+    for i in 0..RATE {
+        bytes[i * 8..i * 8 + 8].copy_from_slice(&state[i].to_le_bytes());
+    }
+    bytes
+}
+
+fn overwrite_rate(state: &mut [u64; 8], bytes: &[u8; BLOCK_BYTES]) {
+    for i in 0..RATE {
+        state[i] = u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+    }
+}
+
+// A final block shorter than BLOCK_BYTES leaves the rest of the rate
+// holding raw, un-XORed keystream unless we bind the true length in --
+// otherwise a truncated ciphertext can slip through wherever the missing
+// keystream byte happens to equal the dropped ciphertext byte. XOR a 0x80
+// marker just past the real data, Ascon-style, so partial blocks of
+// different lengths (and a full block) can never produce the same duplex
+// state.
+fn pad_partial_rate_block(rate_bytes: &mut [u8; BLOCK_BYTES], data_len: usize) {
+    if data_len < BLOCK_BYTES {
+        rate_bytes[data_len] ^= 0x80;
+    }
+}
+
+// Duplex-encrypt `plaintext`, XORing each rate-sized block into the rate to
+// produce ciphertext of the same length, then overwriting the rate with
+// that ciphertext before permuting for the next block.
+fn duplex_encrypt(state: &mut [u64; 8], plaintext: &[u8]) -> Vec<u8> {
+    state[RATE] ^= DOMAIN_PT;
+    if plaintext.is_empty() {
+        // No block in the loop below would otherwise diffuse the domain
+        // separator before `finalize_tag` XORs in DOMAIN_FINAL at the same
+        // state word.
+        permute::<NUM_ROUNDS>(state, &[]);
+    }
+    let mut ciphertext = Vec::with_capacity(plaintext.len());
+    for chunk in plaintext.chunks(BLOCK_BYTES) {
+        let keystream = rate_keystream(state);
+        let mut block_ct = vec![0u8; chunk.len()];
+        for i in 0..chunk.len() {
+            block_ct[i] = chunk[i] ^ keystream[i];
+        }
+
+        let mut rate_bytes = keystream;
+        rate_bytes[..chunk.len()].copy_from_slice(&block_ct);
+        pad_partial_rate_block(&mut rate_bytes, chunk.len());
+        overwrite_rate(state, &rate_bytes);
+        permute::<NUM_ROUNDS>(state, &block_ct);
+
+        ciphertext.extend_from_slice(&block_ct);
+    }
+    ciphertext
+}
+
+// The decryption-side dual of `duplex_encrypt`: recovers plaintext from
+// ciphertext while driving the duplex state identically, so the same
+// finalization yields the same tag when the ciphertext is authentic.
+fn duplex_decrypt(state: &mut [u64; 8], ciphertext: &[u8]) -> Vec<u8> {
+    state[RATE] ^= DOMAIN_PT;
+    if ciphertext.is_empty() {
+        // Mirrors `duplex_encrypt`'s empty-input diffusion so the two sides
+        // drive the duplex state identically.
+        permute::<NUM_ROUNDS>(state, &[]);
+    }
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    for chunk in ciphertext.chunks(BLOCK_BYTES) {
+        let keystream = rate_keystream(state);
+        let mut block_pt = vec![0u8; chunk.len()];
+        for i in 0..chunk.len() {
+            block_pt[i] = chunk[i] ^ keystream[i];
+        }
+
+        let mut rate_bytes = keystream;
+        rate_bytes[..chunk.len()].copy_from_slice(chunk);
+        pad_partial_rate_block(&mut rate_bytes, chunk.len());
+        overwrite_rate(state, &rate_bytes);
+        permute::<NUM_ROUNDS>(state, chunk);
+
+        plaintext.extend_from_slice(&block_pt);
+    }
+    plaintext
+}
+
+// Re-absorb the key and permute once more so the tag is bound to it (not
+// just to the associated data / ciphertext history), then squeeze the
+// capacity words out as the authentication tag.
+fn finalize_tag(mut state: [u64; 8], key: &[u8]) -> [u8; TAG_BYTES] {
+    state[RATE] ^= DOMAIN_FINAL;
+    for (i, chunk) in key.chunks(8).take(CAPACITY).enumerate() {
+        state[RATE + i] ^= word_from_le_bytes(chunk);
+    }
+    permute::<NUM_ROUNDS>(&mut state, key);
+
+    let mut tag = [0u8; TAG_BYTES];
+    for i in 0..CAPACITY {
+        tag[i * 8..i * 8 + 8].copy_from_slice(&state[RATE + i].to_le_bytes());
+    }
+    tag
+}
+
+// Constant-time byte comparison, used to reject forged tags without leaking
+// how many leading bytes matched via a timing side channel.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+fn encrypt(key: &[u8], nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> (Vec<u8>, [u8; TAG_BYTES]) {
+    let mut state = init_aead_state(key, nonce);
+    absorb_associated_data(&mut state, associated_data);
+    let ciphertext = duplex_encrypt(&mut state, plaintext);
+    let tag = finalize_tag(state, key);
+    (ciphertext, tag)
+}
+
+// Returns `None` if the tag does not authenticate; callers must not use the
+// returned plaintext (there is none) when decryption fails.
+fn decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; TAG_BYTES],
+) -> Option<Vec<u8>> {
+    let mut state = init_aead_state(key, nonce);
+    absorb_associated_data(&mut state, associated_data);
+    let plaintext = duplex_decrypt(&mut state, ciphertext);
+    let expected_tag = finalize_tag(state, key);
+
+    if constant_time_eq(&expected_tag, tag) {
+        Some(plaintext)
+    } else {
+        None
+    }
+}
+
 // Helper function to print hashes
 fn print_hash(label: &str, input: &[u8], hashval: &[u64; 8]) {
     print!("{} ({} bytes): ", label, input.len());
@@ -173,62 +618,55 @@ fn main() {
     let _ = hash(&[&bigdata]);
     println!("Hash time for 10MB: {:.3?}", start.elapsed());
 
-    // Tests
-    avalanche_test();
-    collision_test();
+    println!("=== Streaming HashEngine ===");
+    let mut engine = HashEngine::<NUM_ROUNDS>::new();
+    engine.input(b"some medium length ");
+    engine.input(b"data");
+    let fed = engine.len();
+    let streamed = engine.finalize();
+    let oneshot = hash(&[b"some medium length data"]);
+    println!("Streamed == one-shot: {} (bytes fed: {})", streamed == oneshot, fed);
+
+    println!("=== Keyed Hash / MAC ===");
+    let key = b"a secret key used to seed the capacity";
+    let tag_a = keyed_hash(key, &[b"message"]);
+    let tag_b = keyed_hash(b"a different secret key used here!!!!!", &[b"message"]);
+    let fast_tag = keyed_hash_fast(key, &[b"message"]);
+    println!("Tags differ across keys: {}", tag_a != tag_b);
+    println!("Full vs fast-round tags differ: {}", tag_a != fast_tag);
+
+    println!("=== Sponge AEAD ===");
+    let aead_key = b"0123456789abcdef0123456789abcdef";
+    let nonce = b"unique-nonce";
+    let ad = b"header metadata";
+    let plaintext = b"the quick brown fox jumps over the lazy dog";
+    let (ciphertext, tag) = encrypt(aead_key, nonce, ad, plaintext);
+    let recovered = decrypt(aead_key, nonce, ad, &ciphertext, &tag).expect("tag must verify");
+    println!("Decrypt round-trips: {}", recovered == plaintext);
+
+    let mut bad_tag = tag;
+    bad_tag[0] ^= 0x01;
+    println!("Tampered tag rejected: {}", decrypt(aead_key, nonce, ad, &ciphertext, &bad_tag).is_none());
+
+    println!("=== XOF ===");
+    let mut xof_out = [0u8; 100];
+    xof(&[b"expand me"], &mut xof_out);
+    let mut prefix = [0u8; 32];
+    xof(&[b"expand me"], &mut prefix);
+    println!(
+        "XOF output is deterministic and prefix-stable: {}",
+        xof_out[..32] == prefix
+    );
+
+    println!("=== HashMap Integration ===");
+    let mut map: std::collections::HashMap<&str, i32, FrostBuildHasher> =
+        std::collections::HashMap::with_hasher(FrostBuildHasher::new());
+    map.insert("one", 1);
+    map.insert("two", 2);
+    println!("HashMap<_, _, FrostBuildHasher> lookup: {:?}", map.get("two"));
+
+    // Benchmarks
     speed_test();
-    determinism_test();
-    differential_test();
-}
-
-
-// === Tests ===
-
-// Avalanche test function
-fn avalanche_test() {
-    let input = b"hello world";
-    let mut modified = input.clone().to_vec();
-    modified[0] ^= 0x01;
-
-    let h1 = hash(&[input]);
-    let h2 = hash(&[&modified]);
-
-    println!("--- Avalanche Test ---");
-    print_hash("Original", input, &h1);
-    print_hash("Modified", &modified, &h2);
-
-    let mut diff_bits = 0;
-    for i in 0..8 {
-        diff_bits += (h1[i] ^ h2[i]).count_ones();
-    }
-    println!("Differing bits: {}", diff_bits);
-}
-
-// Collision test: scan many similar inputs for collisions
-fn collision_test() {
-    println!("--- Collision Test ---");
-    let base = b"collision_test_base_string";
-    let mut collisions = 0;
-    let tries = 200;
-
-    for i in 0..tries {
-        let mut input = base.to_vec();
-        input.push(i as u8);
-        let h1 = hash(&[&input]);
-
-        for j in (i + 1)..tries {
-            let mut input2 = base.to_vec();
-            input2.push(j as u8);
-            let h2 = hash(&[&input2]);
-
-            if h1 == h2 {
-                println!("Collision found between inputs {} and {}", i, j);
-                collisions += 1;
-            }
-        }
-    }
-
-    println!("Total collisions in {} tries: {}", tries, collisions);
 }
 
 // Speed test for various input sizes
@@ -244,42 +682,132 @@ fn speed_test() {
     }
 }
 
-// Determinism test: same input -> same output
-fn determinism_test() {
-    println!("--- Determinism Test ---");
-    let input = b"determinism_test_input_data";
-    let h1 = hash(&[input]);
-    let h2 = hash(&[input]);
-    assert_eq!(h1, h2);
-    println!("Determinism test passed!");
-}
-
-// Differential Analysis: Evaluates Avalanche Effect Across Input Variations
-fn differential_test() {
-    let base = b"diff_test_input_data_for_hash";
-    let mut total_diff = 0u32;
-    let mut pairs = 0u32;
+// === Hash quality tests ===
+//
+// Ported from ahash's `hash_quality_test` battery: quantitative bounds on
+// diffusion instead of numbers a human has to eyeball, so a regression
+// fails the build rather than scrolling past in stdout.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // How far a flip probability may drift from the ideal 0.5 before it is
+    // considered a diffusion weakness.
+    const AVALANCHE_TOLERANCE: f64 = 0.12;
+    const INDEPENDENCE_TOLERANCE: f64 = 0.1;
+
+    // Flips every bit of a base input in turn and checks that each output
+    // bit flips close to half the time across those trials.
+    #[test]
+    fn avalanche_bit_flip_probability() {
+        let base = b"hash quality avalanche test vector, long enough to span blocks";
+        let base_hash = hash(&[base]);
+        let trials = base.len() * 8;
+        let mut flips = [0u32; 8 * 64];
+
+        for bit in 0..trials {
+            let mut modified = base.to_vec();
+            modified[bit / 8] ^= 1 << (bit % 8);
+            let modified_hash = hash(&[&modified]);
+
+            for word in 0..8 {
+                let diff = base_hash[word] ^ modified_hash[word];
+                for b in 0..64 {
+                    if (diff >> b) & 1 == 1 {
+                        flips[word * 64 + b] += 1;
+                    }
+                }
+            }
+        }
 
-    for i in 0..base.len() {
-        for b in 0u8..=255 {
-            let input1 = base.to_vec();
-            let mut input2 = base.to_vec();
-            input2[i] = b;
+        for (i, &count) in flips.iter().enumerate() {
+            let p = f64::from(count) / trials as f64;
+            assert!(
+                (p - 0.5).abs() < AVALANCHE_TOLERANCE,
+                "output bit {i} flipped with probability {p:.3}, expected close to 0.5"
+            );
+        }
+    }
 
-            let h1 = hash(&[&input1]);
-            let h2 = hash(&[&input2]);
+    // Checks that pairs of output words don't move in lockstep: the
+    // bit-level agreement between any two output words, across many
+    // independent inputs, should sit close to the 0.5 expected of
+    // independent coin flips.
+    #[test]
+    fn output_word_independence() {
+        let hashes: Vec<[u64; 8]> = (0..256)
+            .map(|i| hash(&[format!("independence-test-input-{i}").as_bytes()]))
+            .collect();
+
+        for a in 0..8 {
+            for b in (a + 1)..8 {
+                let mut agree = 0u32;
+                let mut total = 0u32;
+                for h in &hashes {
+                    for bit in 0..64 {
+                        if (h[a] >> bit) & 1 == (h[b] >> bit) & 1 {
+                            agree += 1;
+                        }
+                        total += 1;
+                    }
+                }
+                let p = f64::from(agree) / f64::from(total);
+                assert!(
+                    (p - 0.5).abs() < INDEPENDENCE_TOLERANCE,
+                    "output words {a} and {b} agree with probability {p:.3}, expected close to 0.5"
+                );
+            }
+        }
+    }
 
-            let mut diff_bits = 0;
-            for idx in 0..8 {
-                diff_bits += (h1[idx] ^ h2[idx]).count_ones();
+    // Flipping a single key bit should change the tag about as thoroughly as
+    // flipping a message bit does; a weak key schedule would leave the tag
+    // nearly unchanged for some key bits.
+    #[test]
+    fn keyed_hash_key_sensitivity() {
+        let message = b"keyed sensitivity test message";
+        let base_key = b"0123456789abcdef0123456789abcdef";
+        let base_tag = keyed_hash(base_key, &[message]);
+        let trials = base_key.len() * 8;
+
+        let mut total_diff_bits = 0u32;
+        for bit in 0..trials {
+            let mut modified_key = base_key.to_vec();
+            modified_key[bit / 8] ^= 1 << (bit % 8);
+            let modified_tag = keyed_hash(&modified_key, &[message]);
+
+            for i in 0..CAPACITY {
+                total_diff_bits += (base_tag[i] ^ modified_tag[i]).count_ones();
             }
-            total_diff += diff_bits;
-            pairs += 1;
         }
+
+        let tag_bits = CAPACITY * 64;
+        let avg_diff_bits = f64::from(total_diff_bits) / trials as f64;
+        assert!(
+            avg_diff_bits > (tag_bits as f64) * 0.4,
+            "average differing tag bits per key-bit flip was only {avg_diff_bits:.1}, expected > {:.1} out of {tag_bits}",
+            (tag_bits as f64) * 0.4
+        );
     }
 
-    println!("Differential test average differing bits: {}", total_diff / pairs);
-}
+    #[test]
+    fn collision_free_over_similar_inputs() {
+        let base = b"collision_test_base_string";
+        let tries = 200u8;
+        let mut seen = std::collections::HashSet::new();
 
+        for i in 0..tries {
+            let mut input = base.to_vec();
+            input.push(i);
+            assert!(seen.insert(hash(&[&input])), "collision found for suffix byte {i}");
+        }
+    }
+
+    #[test]
+    fn deterministic_output() {
+        let input = b"determinism_test_input_data";
+        assert_eq!(hash(&[input]), hash(&[input]));
+    }
+}
 
 